@@ -0,0 +1,65 @@
+use std::sync::{Arc, RwLock};
+
+use axum::body::Body;
+use axum::extract::State;
+use axum::http::{HeaderMap, Request, StatusCode};
+use axum::middleware::Next;
+use axum::response::Response;
+
+/// The shared secret, if one is configured. Wrapped in an `RwLock` rather
+/// than loaded per-request since it never changes after startup.
+pub type AuthSecret = Arc<RwLock<Option<String>>>;
+
+/// Reads the secret once from the `AUTH_SECRET` env var, falling back to an
+/// `auth_secret` file. No secret configured means the guard lets every
+/// request through, which keeps local/dev usage working without setup.
+pub fn load_secret() -> AuthSecret {
+    let secret = std::env::var("AUTH_SECRET").ok().or_else(|| {
+        std::fs::read_to_string("auth_secret")
+            .ok()
+            .map(|s| s.trim().to_string())
+    });
+    Arc::new(RwLock::new(secret))
+}
+
+/// Axum middleware guarding mutating routes: rejects with `401` unless the
+/// request carries the configured secret via `Authorization: Bearer <secret>`
+/// or `X-Auth: <secret>`.
+pub async fn require_auth(
+    State(secret): State<AuthSecret>,
+    req: Request<Body>,
+    next: Next<Body>,
+) -> Result<Response, StatusCode> {
+    let secret = secret.read().unwrap().clone();
+    let Some(secret) = secret else {
+        return Ok(next.run(req).await);
+    };
+
+    match extract_token(req.headers()) {
+        Some(token) if constant_time_eq(token.as_bytes(), secret.as_bytes()) => {
+            Ok(next.run(req).await)
+        }
+        _ => Err(StatusCode::UNAUTHORIZED),
+    }
+}
+
+fn extract_token(headers: &HeaderMap) -> Option<String> {
+    if let Some(value) = headers.get("authorization").and_then(|v| v.to_str().ok()) {
+        if let Some(token) = value.strip_prefix("Bearer ") {
+            return Some(token.to_string());
+        }
+    }
+    headers
+        .get("x-auth")
+        .and_then(|v| v.to_str().ok())
+        .map(|s| s.to_string())
+}
+
+/// Compares two byte strings in constant time to avoid leaking the secret
+/// through response-time differences.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b.iter()).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
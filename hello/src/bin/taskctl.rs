@@ -0,0 +1,128 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use clap::{Parser, Subcommand};
+use uuid::Uuid;
+
+use hello::dbctx::{now_millis, DbCtx};
+use hello::worker::{run_analysis, InFlight};
+use hello::{Task, TaskStatus};
+
+/// Manage the task store directly against its SQLite file, without going
+/// through the HTTP API or requiring the server to be running.
+#[derive(Parser)]
+#[command(name = "taskctl")]
+struct Cli {
+    /// Path to the SQLite database used by the server.
+    #[arg(long, default_value = "./state.db")]
+    db_path: String,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Create a new task and run Claude analysis on it in this process.
+    Add { title: String },
+    /// List tasks, optionally filtered by status or priority.
+    List {
+        #[arg(long)]
+        status: Option<String>,
+        #[arg(long)]
+        priority: Option<String>,
+    },
+    /// Show a single task by id.
+    Show { id: Uuid },
+    /// Mark a task completed.
+    Complete { id: Uuid },
+    /// Delete a task.
+    Delete { id: Uuid },
+    /// Run Claude analysis on a task again, in this process; the server's
+    /// analysis queue is not used.
+    Reanalyze { id: Uuid },
+}
+
+#[tokio::main]
+async fn main() {
+    let cli = Cli::parse();
+    let db = DbCtx::new(&cli.db_path).expect("failed to open database");
+
+    match cli.command {
+        Command::Add { title } => {
+            let task = Task {
+                id: Uuid::new_v4(),
+                title,
+                completed: false,
+                priority: None,
+                estimated_time: None,
+                status: TaskStatus::Pending,
+                created_time: now_millis(),
+                completed_time: None,
+            };
+            db.insert_task(&task).expect("failed to insert task");
+
+            match run_analysis(&db, task.id, &new_in_flight()).await {
+                Ok(analyzed) if analyzed.status == TaskStatus::Failed => {
+                    eprintln!("task created but analysis failed for {}", task.id);
+                    print_task(&analyzed);
+                }
+                Ok(analyzed) => print_task(&analyzed),
+                Err(_) => {
+                    eprintln!("task created but analysis failed for {}", task.id);
+                    print_task(&task);
+                }
+            }
+        }
+        Command::List { status, priority } => {
+            let tasks = db.list_tasks().expect("failed to list tasks");
+            for task in tasks
+                .iter()
+                .filter(|t| status.as_deref().is_none_or(|s| t.status.as_str() == s))
+                .filter(|t| priority.as_deref().is_none_or(|p| t.priority.as_deref() == Some(p)))
+            {
+                print_task(task);
+            }
+        }
+        Command::Show { id } => match db.get_task(id).expect("failed to read task") {
+            Some(task) => print_task(&task),
+            None => eprintln!("no task with id {id}"),
+        },
+        Command::Complete { id } => {
+            match db.get_task(id).expect("failed to read task") {
+                Some(mut task) => {
+                    task.completed = true;
+                    task.completed_time = Some(now_millis());
+                    db.update_task(&task).expect("failed to update task");
+                    print_task(&task);
+                }
+                None => eprintln!("no task with id {id}"),
+            }
+        }
+        Command::Delete { id } => {
+            if db.delete_task(id).expect("failed to delete task") {
+                println!("deleted {id}");
+            } else {
+                eprintln!("no task with id {id}");
+            }
+        }
+        Command::Reanalyze { id } => match run_analysis(&db, id, &new_in_flight()).await {
+            Ok(task) if task.status == TaskStatus::Failed => {
+                eprintln!("analysis failed for {id}");
+                print_task(&task);
+            }
+            Ok(task) => print_task(&task),
+            Err(_) => eprintln!("failed to analyze task {id}"),
+        },
+    }
+}
+
+/// A fresh, empty `InFlight` map for a one-shot CLI analysis call — there's
+/// no running queue or SSE subscribers outside the server to track.
+fn new_in_flight() -> InFlight {
+    Arc::new(Mutex::new(HashMap::new()))
+}
+
+fn print_task(task: &Task) {
+    println!("{}", serde_json::to_string_pretty(task).unwrap());
+}
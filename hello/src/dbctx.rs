@@ -0,0 +1,131 @@
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use rusqlite::{params, Connection, OptionalExtension};
+use uuid::Uuid;
+
+use crate::{Task, TaskStatus};
+
+/// Wraps a single SQLite connection behind a mutex so it can be shared across
+/// the axum handlers without each request opening its own connection.
+#[derive(Clone)]
+pub struct DbCtx {
+    conn: Arc<Mutex<Connection>>,
+}
+
+impl DbCtx {
+    pub fn new(path: &str) -> rusqlite::Result<Self> {
+        let conn = Connection::open(path)?;
+        let ctx = Self {
+            conn: Arc::new(Mutex::new(conn)),
+        };
+        ctx.init_schema()?;
+        Ok(ctx)
+    }
+
+    fn init_schema(&self) -> rusqlite::Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS tasks (
+                id              TEXT PRIMARY KEY,
+                title           TEXT NOT NULL,
+                completed       INTEGER NOT NULL,
+                priority        TEXT,
+                estimated_time  TEXT,
+                status          TEXT NOT NULL,
+                created_time    INTEGER NOT NULL,
+                completed_time  INTEGER
+            )",
+            [],
+        )?;
+        Ok(())
+    }
+
+    pub fn insert_task(&self, task: &Task) -> rusqlite::Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT INTO tasks (id, title, completed, priority, estimated_time, status, created_time, completed_time)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![
+                task.id.to_string(),
+                task.title,
+                task.completed,
+                task.priority,
+                task.estimated_time,
+                task.status.as_str(),
+                task.created_time,
+                task.completed_time,
+            ],
+        )?;
+        Ok(())
+    }
+
+    pub fn list_tasks(&self) -> rusqlite::Result<Vec<Task>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT id, title, completed, priority, estimated_time, status, created_time, completed_time
+             FROM tasks ORDER BY created_time",
+        )?;
+        let rows = stmt.query_map([], row_to_task)?;
+        rows.collect()
+    }
+
+    pub fn get_task(&self, id: Uuid) -> rusqlite::Result<Option<Task>> {
+        let conn = self.conn.lock().unwrap();
+        conn.query_row(
+            "SELECT id, title, completed, priority, estimated_time, status, created_time, completed_time
+             FROM tasks WHERE id = ?1",
+            params![id.to_string()],
+            row_to_task,
+        )
+        .optional()
+    }
+
+    pub fn update_task(&self, task: &Task) -> rusqlite::Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "UPDATE tasks SET title = ?2, completed = ?3, priority = ?4, estimated_time = ?5,
+             status = ?6, created_time = ?7, completed_time = ?8 WHERE id = ?1",
+            params![
+                task.id.to_string(),
+                task.title,
+                task.completed,
+                task.priority,
+                task.estimated_time,
+                task.status.as_str(),
+                task.created_time,
+                task.completed_time,
+            ],
+        )?;
+        Ok(())
+    }
+
+    pub fn delete_task(&self, id: Uuid) -> rusqlite::Result<bool> {
+        let conn = self.conn.lock().unwrap();
+        let affected = conn.execute("DELETE FROM tasks WHERE id = ?1", params![id.to_string()])?;
+        Ok(affected > 0)
+    }
+}
+
+fn row_to_task(row: &rusqlite::Row) -> rusqlite::Result<Task> {
+    let id: String = row.get(0)?;
+    Ok(Task {
+        id: Uuid::parse_str(&id).map_err(|e| {
+            rusqlite::Error::FromSqlConversionFailure(0, rusqlite::types::Type::Text, Box::new(e))
+        })?,
+        title: row.get(1)?,
+        completed: row.get(2)?,
+        priority: row.get(3)?,
+        estimated_time: row.get(4)?,
+        status: TaskStatus::from_db_str(&row.get::<_, String>(5)?),
+        created_time: row.get(6)?,
+        completed_time: row.get(7)?,
+    })
+}
+
+pub fn now_millis() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as i64
+}
@@ -0,0 +1,47 @@
+pub mod auth;
+pub mod dbctx;
+pub mod notifier;
+pub mod worker;
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum TaskStatus {
+    Pending,
+    Analyzing,
+    Analyzed,
+    Failed,
+}
+
+impl TaskStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TaskStatus::Pending => "pending",
+            TaskStatus::Analyzing => "analyzing",
+            TaskStatus::Analyzed => "analyzed",
+            TaskStatus::Failed => "failed",
+        }
+    }
+
+    pub fn from_db_str(s: &str) -> Self {
+        match s {
+            "analyzing" => TaskStatus::Analyzing,
+            "analyzed" => TaskStatus::Analyzed,
+            "failed" => TaskStatus::Failed,
+            _ => TaskStatus::Pending,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Task {
+    pub id: Uuid,
+    pub title: String,
+    pub completed: bool,
+    pub priority: Option<String>,
+    pub estimated_time: Option<String>,
+    pub status: TaskStatus,
+    pub created_time: i64,
+    pub completed_time: Option<i64>,
+}
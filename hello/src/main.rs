@@ -1,48 +1,86 @@
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+
 use axum::{
+    response::sse::{Event, Sse},
     routing::{get, post},
     http::StatusCode,
+    middleware,
     Json, Router,
     extract::{State, Path},
 };
-use serde::{Deserialize, Serialize};
-use std::sync::{Arc, Mutex};
+use futures_util::{stream, Stream, StreamExt};
+use serde::Deserialize;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
 use uuid::Uuid;
 use dotenv::dotenv;
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
-struct Task {
-    id: Uuid,
-    title: String,
-    completed: bool,
-    priority: Option<String>,
-    estimated_time: Option<String>,
-}
+use hello::auth::{self, AuthSecret};
+use hello::dbctx::{now_millis, DbCtx};
+use hello::notifier::{self, NotifierConfig, TaskEvent};
+use hello::worker::{spawn_analysis_worker, InFlight};
+use hello::{Task, TaskStatus};
+
+type AnalysisStream = Pin<Box<dyn Stream<Item = Result<Event, Infallible>> + Send>>;
 
 #[derive(Debug, Deserialize)]
 struct CreateTask {
     title: String,
 }
 
-type Db = Arc<Mutex<Vec<Task>>>;
+#[derive(Clone)]
+struct AppState {
+    db: DbCtx,
+    analysis_tx: mpsc::Sender<Uuid>,
+    in_flight: InFlight,
+    notifiers: Arc<Vec<NotifierConfig>>,
+    auth_secret: AuthSecret,
+}
 
 #[tokio::main]
 async fn main() {
     dotenv().ok();
-    let db = Arc::new(Mutex::new(Vec::new()));
+    let db_path = std::env::var("DATABASE_PATH").unwrap_or_else(|_| "state.db".to_string());
+    let db = DbCtx::new(&db_path).expect("failed to open database");
+
+    let notifiers = Arc::new(notifier::load_from_env());
+
+    let (analysis_tx, analysis_rx) = mpsc::channel(100);
+    let in_flight: InFlight = Arc::new(Mutex::new(HashMap::new()));
+    spawn_analysis_worker(db.clone(), analysis_rx, in_flight.clone(), notifiers.clone());
+
+    let state = AppState {
+        db,
+        analysis_tx,
+        in_flight,
+        notifiers,
+        auth_secret: auth::load_secret(),
+    };
+
+    let public = Router::new()
+        .route("/tasks", get(|state: State<AppState>| async move { list_tasks(state).await }))
+        .route("/tasks/:id", get(|state: State<AppState>, path: Path<Uuid>| async move { get_task(state, path).await }))
+        .route("/tasks/:id/analysis", get(|state: State<AppState>, path: Path<Uuid>| async move { stream_analysis(state, path).await }));
 
-    let app = Router::new()
-        .route("/tasks", 
-            get(|state: State<Db>| async move { list_tasks(state).await })
-            .post(|state: State<Db>, payload: Json<CreateTask>| async move {
+    let protected = Router::new()
+        .route("/tasks",
+            post(|state: State<AppState>, payload: Json<CreateTask>| async move {
                 create_task(state, payload).await
             })
         )
-        .route("/tasks/:id", 
-            get(|state: State<Db>, path: Path<Uuid>| async move { get_task(state, path).await })
-            .patch(|state: State<Db>, path: Path<Uuid>, payload: Json<Task>| async move { update_task(state, path, payload).await })
-            .delete(|state: State<Db>, path: Path<Uuid>| async move { delete_task(state, path).await })
+        .route("/tasks/:id",
+            axum::routing::patch(|state: State<AppState>, path: Path<Uuid>, payload: Json<Task>| async move { update_task(state, path, payload).await })
+            .delete(|state: State<AppState>, path: Path<Uuid>| async move { delete_task(state, path).await })
         )
-        .with_state(db);
+        .route_layer(middleware::from_fn_with_state(
+            state.auth_secret.clone(),
+            auth::require_auth,
+        ));
+
+    let app = public.merge(protected).with_state(state);
 
     axum::Server::bind(&"0.0.0.0:3000".parse().unwrap())
         .serve(app.into_make_service())
@@ -50,114 +88,151 @@ async fn main() {
         .unwrap();
 }
 
-async fn list_tasks(State(db): State<Db>) -> Json<Vec<Task>> {
-    let tasks = db.lock().unwrap().clone();
-    Json(tasks)
+async fn list_tasks(State(state): State<AppState>) -> Result<Json<Vec<Task>>, StatusCode> {
+    state
+        .db
+        .list_tasks()
+        .map(Json)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
 }
 
 async fn create_task(
-    State(db): State<Db>,
+    State(state): State<AppState>,
     Json(payload): Json<CreateTask>,
-) -> (StatusCode, Json<Task>) {
+) -> Result<(StatusCode, Json<Task>), StatusCode> {
     let task = Task {
         id: Uuid::new_v4(),
         title: payload.title,
         completed: false,
         priority: None,
         estimated_time: None,
+        status: TaskStatus::Pending,
+        created_time: now_millis(),
+        completed_time: None,
     };
-    
-    let task_with_ai = match analyze_task_with_claude(task.clone()).await {
-        Ok(analyzed_task) => analyzed_task,
-        Err(_) => task.clone(),
+
+    state
+        .db
+        .insert_task(&task)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    // Only enqueue if this id isn't already tracked as in flight, so a task
+    // is never handed to the worker twice.
+    let newly_tracked = match state.in_flight.lock().unwrap().entry(task.id) {
+        std::collections::hash_map::Entry::Occupied(_) => false,
+        std::collections::hash_map::Entry::Vacant(entry) => {
+            entry.insert(Vec::new());
+            true
+        }
     };
 
-    // MutexGuardの生存期間を短くするため、スコープを制限します
-    {
-        let mut db_guard = db.lock().unwrap();
-        db_guard.push(task_with_ai.clone());
+    if newly_tracked && state.analysis_tx.send(task.id).await.is_err() {
+        // The worker is gone, so nothing will ever pick this id up: drop the
+        // in-flight entry and mark the task failed instead of leaving it
+        // stuck `Pending` with orphaned SSE subscribers.
+        state.in_flight.lock().unwrap().remove(&task.id);
+        let mut task = task;
+        task.status = TaskStatus::Failed;
+        let _ = state.db.update_task(&task);
+        return Ok((StatusCode::CREATED, Json(task)));
     }
 
-    (StatusCode::CREATED, Json(task_with_ai))
+    Ok((StatusCode::CREATED, Json(task)))
 }
 
 async fn get_task(
-    State(db): State<Db>,
+    State(state): State<AppState>,
     Path(id): Path<Uuid>,
 ) -> Result<Json<Task>, StatusCode> {
-    let db = db.lock().unwrap();
-    db.iter()
-        .find(|task| task.id == id)
-        .cloned()
+    state
+        .db
+        .get_task(id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
         .map(Json)
         .ok_or(StatusCode::NOT_FOUND)
 }
 
 async fn update_task(
-    State(db): State<Db>,
+    State(state): State<AppState>,
     Path(id): Path<Uuid>,
     Json(payload): Json<Task>,
 ) -> Result<Json<Task>, StatusCode> {
-    let mut db = db.lock().unwrap();
-    if let Some(task) = db.iter_mut().find(|t| t.id == id) {
-        *task = payload;
-        Ok(Json(task.clone()))
-    } else {
-        Err(StatusCode::NOT_FOUND)
+    if payload.id != id {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let existing = state
+        .db
+        .get_task(id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    let mut payload = payload;
+    let newly_completed = payload.completed && !existing.completed;
+    if newly_completed {
+        payload.completed_time = Some(now_millis());
+    }
+
+    state
+        .db
+        .update_task(&payload)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    if newly_completed {
+        notifier::notify(&state.notifiers, payload.clone(), TaskEvent::Completed);
     }
+
+    Ok(Json(payload))
 }
 
 async fn delete_task(
-    State(db): State<Db>,
+    State(state): State<AppState>,
     Path(id): Path<Uuid>,
-) -> StatusCode {
-    let mut db = db.lock().unwrap();
-    let len = db.len();
-    db.retain(|t| t.id != id);
-    if db.len() != len {
-        StatusCode::NO_CONTENT
+) -> Result<StatusCode, StatusCode> {
+    let deleted = state
+        .db
+        .delete_task(id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    if deleted {
+        Ok(StatusCode::NO_CONTENT)
     } else {
-        StatusCode::NOT_FOUND
+        Ok(StatusCode::NOT_FOUND)
     }
 }
 
-async fn analyze_task_with_claude(task: Task) -> Result<Task, StatusCode> {
-    let claude_api_key = std::env::var("CLAUDE_API_KEY").expect("CLAUDE_API_KEY must be set");
-    let client = reqwest::Client::new();
-    let prompt = format!(
-        "Analyze the following task and suggest a priority level (High, Medium, Low) and estimated time to complete (in hours): {}",
-        task.title
-    );
-
-    let response = client
-        .post("https://api.anthropic.com/v1/completions")
-        .header("Content-Type", "application/json")
-        .header("X-API-Key", claude_api_key)
-        .json(&serde_json::json!({
-            "model": "claude-2",
-            "prompt": prompt,
-            "max_tokens_to_sample": 150,
-        }))
-        .send()
-        .await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
-        .json::<serde_json::Value>()
-        .await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
-
-    let ai_response = response["completion"].as_str().unwrap_or("");
-    let mut task = task;
-    if ai_response.contains("High") {
-        task.priority = Some("High".to_string());
-    } else if ai_response.contains("Medium") {
-        task.priority = Some("Medium".to_string());
-    } else if ai_response.contains("Low") {
-        task.priority = Some("Low".to_string());
-    }
+async fn stream_analysis(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<Sse<AnalysisStream>, StatusCode> {
+    // Only ever attach to an `in_flight` entry that already exists — the
+    // worker removes it only after the final status is written to the db,
+    // so this can't race with the task finishing between a status check and
+    // subscribing (which would otherwise resurrect a removed id that
+    // nothing broadcasts to or cleans up).
+    let (tx, rx) = mpsc::channel(32);
+    let attached = {
+        let mut in_flight = state.in_flight.lock().unwrap();
+        match in_flight.get_mut(&id) {
+            Some(subscribers) => {
+                subscribers.push(tx);
+                true
+            }
+            None => false,
+        }
+    };
 
-    if let Some(time) = ai_response.split("hours").next().and_then(|s| s.split_whitespace().last()) {
-        task.estimated_time = Some(format!("{} hours", time));
+    if attached {
+        let events = ReceiverStream::new(rx).map(|chunk| Ok(Event::default().data(chunk)));
+        return Ok(Sse::new(Box::pin(events)));
     }
 
-    Ok(task)
-}
\ No newline at end of file
+    let task = state
+        .db
+        .get_task(id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    let payload = serde_json::to_string(&task).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let event = stream::once(async move { Ok(Event::default().data(payload)) });
+    Ok(Sse::new(Box::pin(event)))
+}
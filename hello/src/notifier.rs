@@ -0,0 +1,162 @@
+use lettre::message::Message;
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{SmtpTransport, Transport};
+
+use crate::Task;
+
+/// Where to send task event notifications. Loaded once at startup from env so
+/// a deployment can configure zero, one, or several notifiers at a time.
+#[derive(Clone)]
+pub enum NotifierConfig {
+    Email {
+        smtp_host: String,
+        smtp_user: String,
+        smtp_pass: String,
+        to: String,
+    },
+    Webhook {
+        url: String,
+    },
+}
+
+/// Hand-written so logging a notifier (e.g. on send failure) never leaks
+/// `smtp_pass` into stderr/logs.
+impl std::fmt::Debug for NotifierConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NotifierConfig::Email {
+                smtp_host, to, ..
+            } => f
+                .debug_struct("Email")
+                .field("smtp_host", smtp_host)
+                .field("to", to)
+                .field("smtp_pass", &"<redacted>")
+                .finish(),
+            NotifierConfig::Webhook { url } => f.debug_struct("Webhook").field("url", url).finish(),
+        }
+    }
+}
+
+/// The event that triggered a notification, used to word the message.
+#[derive(Debug, Clone, Copy)]
+pub enum TaskEvent {
+    Completed,
+    HighPriority,
+}
+
+impl TaskEvent {
+    fn summary(&self, task: &Task) -> String {
+        match self {
+            TaskEvent::Completed => format!("Task \"{}\" was marked completed", task.title),
+            TaskEvent::HighPriority => {
+                format!("Task \"{}\" was flagged High priority", task.title)
+            }
+        }
+    }
+}
+
+/// Reads `NOTIFY_EMAIL_*` and `NOTIFY_WEBHOOK_URL` env vars into the list of
+/// configured notifiers. Any notifier whose required vars are absent is
+/// skipped rather than treated as an error.
+pub fn load_from_env() -> Vec<NotifierConfig> {
+    let mut notifiers = Vec::new();
+
+    if let (Ok(smtp_host), Ok(smtp_user), Ok(smtp_pass), Ok(to)) = (
+        std::env::var("NOTIFY_EMAIL_SMTP_HOST"),
+        std::env::var("NOTIFY_EMAIL_SMTP_USER"),
+        std::env::var("NOTIFY_EMAIL_SMTP_PASS"),
+        std::env::var("NOTIFY_EMAIL_TO"),
+    ) {
+        notifiers.push(NotifierConfig::Email {
+            smtp_host,
+            smtp_user,
+            smtp_pass,
+            to,
+        });
+    }
+
+    if let Ok(url) = std::env::var("NOTIFY_WEBHOOK_URL") {
+        notifiers.push(NotifierConfig::Webhook { url });
+    }
+
+    notifiers
+}
+
+/// Fires every configured notifier for `event` without blocking the caller.
+/// Each notifier is isolated: a failing webhook must not stop the email (or
+/// vice versa), so every error is logged and swallowed here.
+pub fn notify(notifiers: &[NotifierConfig], task: Task, event: TaskEvent) {
+    let notifiers = notifiers.to_vec();
+    tokio::spawn(async move {
+        for notifier in notifiers {
+            let result = match &notifier {
+                NotifierConfig::Email { .. } => send_email(&notifier, &task, event).await,
+                NotifierConfig::Webhook { url } => send_webhook(url, &task, event).await,
+            };
+            if let Err(err) = result {
+                eprintln!("notifier failed ({:?}): {}", notifier, err);
+            }
+        }
+    });
+}
+
+/// `SmtpTransport::send` is blocking network I/O, so the actual send runs on
+/// a blocking-pool thread via `spawn_blocking` rather than tying up an async
+/// worker thread.
+async fn send_email(
+    notifier: &NotifierConfig,
+    task: &Task,
+    event: TaskEvent,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let notifier = notifier.clone();
+    let task = task.clone();
+    tokio::task::spawn_blocking(move || send_email_blocking(&notifier, &task, event))
+        .await
+        .map_err(|e| -> Box<dyn std::error::Error> { Box::new(e) })?
+        .map_err(|e| -> Box<dyn std::error::Error> { e.into() })
+}
+
+fn send_email_blocking(notifier: &NotifierConfig, task: &Task, event: TaskEvent) -> Result<(), String> {
+    let NotifierConfig::Email {
+        smtp_host,
+        smtp_user,
+        smtp_pass,
+        to,
+    } = notifier
+    else {
+        return Ok(());
+    };
+
+    let email = Message::builder()
+        .from(smtp_user.parse().map_err(|e: lettre::address::AddressError| e.to_string())?)
+        .to(to.parse().map_err(|e: lettre::address::AddressError| e.to_string())?)
+        .subject("Task update")
+        .body(event.summary(task))
+        .map_err(|e| e.to_string())?;
+
+    let creds = Credentials::new(smtp_user.clone(), smtp_pass.clone());
+    let mailer = SmtpTransport::relay(smtp_host)
+        .map_err(|e| e.to_string())?
+        .credentials(creds)
+        .build();
+    mailer.send(&email).map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+async fn send_webhook(
+    url: &str,
+    task: &Task,
+    event: TaskEvent,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let payload = serde_json::json!({
+        "event": event.summary(task),
+        "task": task,
+    });
+    reqwest::Client::new()
+        .post(url)
+        .json(&payload)
+        .send()
+        .await?
+        .error_for_status()?;
+    Ok(())
+}
@@ -0,0 +1,151 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use axum::http::StatusCode;
+use futures_util::StreamExt;
+use tokio::sync::mpsc;
+use uuid::Uuid;
+
+use crate::dbctx::DbCtx;
+use crate::notifier::{self, NotifierConfig, TaskEvent};
+use crate::{Task, TaskStatus};
+
+/// Ids currently being analyzed, each mapped to the SSE subscribers
+/// (`GET /tasks/:id/analysis`) attached to its live output. An entry exists
+/// for exactly as long as the task is in flight, which both stops a task
+/// from being enqueued twice and tells subscribers when to stop listening.
+pub type InFlight = Arc<Mutex<HashMap<Uuid, Vec<mpsc::Sender<String>>>>>;
+
+/// Spawns the background worker that owns the receiving end of the analysis
+/// queue and performs the (possibly slow) Claude call off the request path.
+pub fn spawn_analysis_worker(
+    db: DbCtx,
+    mut rx: mpsc::Receiver<Uuid>,
+    in_flight: InFlight,
+    notifiers: Arc<Vec<NotifierConfig>>,
+) {
+    tokio::spawn(async move {
+        while let Some(id) = rx.recv().await {
+            if let Ok(task) = run_analysis(&db, id, &in_flight).await {
+                if task.priority.as_deref() == Some("High") {
+                    notifier::notify(&notifiers, task, TaskEvent::HighPriority);
+                }
+            }
+            in_flight.lock().unwrap().remove(&id);
+        }
+    });
+}
+
+/// Runs one task through the Claude analysis pipeline and writes the result
+/// back to the database, transitioning it through `Analyzing` to either
+/// `Analyzed` or `Failed`. Shared by the background worker loop and `taskctl
+/// reanalyze`, which calls this directly with no running queue.
+pub async fn run_analysis(db: &DbCtx, id: Uuid, in_flight: &InFlight) -> Result<Task, StatusCode> {
+    let mut task = db
+        .get_task(id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    task.status = TaskStatus::Analyzing;
+    let _ = db.update_task(&task);
+
+    let task = match analyze_task_with_claude(task.clone(), in_flight).await {
+        Ok(mut analyzed) => {
+            analyzed.status = TaskStatus::Analyzed;
+            analyzed
+        }
+        Err(_) => {
+            task.status = TaskStatus::Failed;
+            task
+        }
+    };
+
+    let _ = db.update_task(&task);
+    Ok(task)
+}
+
+/// Requests a streaming completion from Claude and forwards each chunk to
+/// any SSE clients attached to this task's analysis, so a frontend can show
+/// the reasoning as it's produced instead of waiting for the whole call.
+async fn analyze_task_with_claude(task: Task, in_flight: &InFlight) -> Result<Task, StatusCode> {
+    let claude_api_key =
+        std::env::var("CLAUDE_API_KEY").map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let client = reqwest::Client::new();
+    let prompt = format!(
+        "Analyze the following task and suggest a priority level (High, Medium, Low) and estimated time to complete (in hours): {}",
+        task.title
+    );
+
+    let mut stream = client
+        .post("https://api.anthropic.com/v1/completions")
+        .header("Content-Type", "application/json")
+        .header("X-API-Key", claude_api_key)
+        .json(&serde_json::json!({
+            "model": "claude-2",
+            "prompt": prompt,
+            "max_tokens_to_sample": 150,
+            "stream": true,
+        }))
+        .send()
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .bytes_stream();
+
+    let mut buffer = String::new();
+    let mut ai_response = String::new();
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        buffer.push_str(&String::from_utf8_lossy(&chunk));
+
+        // The stream is SSE framing (`data: {...}` lines), not raw
+        // completion text, so pull the `completion` delta out of each event
+        // rather than forwarding the protocol bytes themselves. A line may
+        // be split across chunks, so only consume complete lines.
+        while let Some(pos) = buffer.find('\n') {
+            let line = buffer[..pos].trim_end_matches('\r').to_string();
+            buffer.drain(..=pos);
+
+            let Some(data) = line.strip_prefix("data: ") else {
+                continue;
+            };
+            if data == "[DONE]" {
+                continue;
+            }
+            let Ok(event) = serde_json::from_str::<serde_json::Value>(data) else {
+                continue;
+            };
+            if let Some(delta) = event["completion"].as_str() {
+                broadcast_chunk(in_flight, task.id, delta);
+                ai_response.push_str(delta);
+            }
+        }
+    }
+
+    let mut task = task;
+    if ai_response.contains("High") {
+        task.priority = Some("High".to_string());
+    } else if ai_response.contains("Medium") {
+        task.priority = Some("Medium".to_string());
+    } else if ai_response.contains("Low") {
+        task.priority = Some("Low".to_string());
+    }
+
+    if let Some(time) = ai_response
+        .split("hours")
+        .next()
+        .and_then(|s| s.split_whitespace().last())
+    {
+        task.estimated_time = Some(format!("{} hours", time));
+    }
+
+    Ok(task)
+}
+
+/// Sends `chunk` to every SSE subscriber currently attached to `id`, dropping
+/// any subscriber whose channel is closed or whose buffer is full.
+fn broadcast_chunk(in_flight: &InFlight, id: Uuid, chunk: &str) {
+    let mut map = in_flight.lock().unwrap();
+    if let Some(subscribers) = map.get_mut(&id) {
+        subscribers.retain(|tx| tx.try_send(chunk.to_string()).is_ok());
+    }
+}